@@ -0,0 +1,55 @@
+// Battery/connection-status decoding for the 0x30 standard input report.
+// The high nibble of byte 2 packs the charge level and charging bit; see
+// https://github.com/dekuNukem/Nintendo_Switch_Reverse_Engineering/blob/master/bluetooth_hid_notes.md
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatteryLevel {
+    Empty,
+    Critical,
+    Low,
+    Medium,
+    Full,
+}
+
+impl BatteryLevel {
+    // How many player LEDs should be lit to represent this level.
+    fn lit_leds(self) -> u8 {
+        return match self {
+            BatteryLevel::Full => 4,
+            BatteryLevel::Medium => 3,
+            BatteryLevel::Low => 2,
+            BatteryLevel::Critical => 1,
+            BatteryLevel::Empty => 0,
+        };
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatteryStatus {
+    pub level: BatteryLevel,
+    pub charging: bool,
+}
+
+// Decodes the battery/connection nibble out of a 0x30 standard input report.
+pub fn read_battery(report: &[u8]) -> BatteryStatus {
+    let nibble = (report[2] >> 4) & 0xf;
+    let charging = nibble & 0x1 != 0;
+    let level = match nibble & 0xe {
+        8 => BatteryLevel::Full,
+        6 => BatteryLevel::Medium,
+        4 => BatteryLevel::Low,
+        2 => BatteryLevel::Critical,
+        _ => BatteryLevel::Empty,
+    };
+    return BatteryStatus { level, charging };
+}
+
+// Builds a player-light bitfield that reflects the battery level as the
+// number of lit LEDs, blinking all of them when the charge is critical
+// and not currently charging.
+pub fn player_lights_for(status: BatteryStatus, blink_on: bool) -> u8 {
+    if status.level == BatteryLevel::Critical && !status.charging {
+        return if blink_on { 0b1111 } else { 0b0000 };
+    }
+    return (1u8 << status.level.lit_leds()) - 1;
+}
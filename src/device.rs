@@ -0,0 +1,77 @@
+// Device identity for the Switch controller family, matched by
+// USB/Bluetooth vendor/product ID instead of the product string (which
+// varies by OS, language and connection type).
+// IDs from https://github.com/dekuNukem/Nintendo_Switch_Reverse_Engineering/blob/master/bluetooth_hid_notes.md
+
+use hidapi::{HidApi, HidDevice};
+
+pub const VENDOR_ID_NINTENDO: u16 = 0x057e;
+pub const PRODUCT_ID_JOYCON_LEFT: u16 = 0x2006;
+pub const PRODUCT_ID_JOYCON_RIGHT: u16 = 0x2007;
+pub const PRODUCT_ID_PRO_CONTROLLER: u16 = 0x2009;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceKind {
+    ProController,
+    JoyConLeft,
+    JoyConRight,
+}
+
+impl DeviceKind {
+    pub fn from_ids(vendor_id: u16, product_id: u16) -> Option<DeviceKind> {
+        if vendor_id != VENDOR_ID_NINTENDO {
+            return None;
+        }
+        return match product_id {
+            PRODUCT_ID_PRO_CONTROLLER => Some(DeviceKind::ProController),
+            PRODUCT_ID_JOYCON_LEFT => Some(DeviceKind::JoyConLeft),
+            PRODUCT_ID_JOYCON_RIGHT => Some(DeviceKind::JoyConRight),
+            _ => None,
+        };
+    }
+}
+
+// Opens the first attached device matching `kind`.
+pub fn find_device(kind: DeviceKind) -> Result<HidDevice, String> {
+    let api = HidApi::new().map_err(|e| e.to_string())?;
+    for device in api.device_list() {
+        if DeviceKind::from_ids(device.vendor_id(), device.product_id()) == Some(kind) {
+            let opened = device.open_device(&api).map_err(|e| e.to_string())?;
+            opened.set_blocking_mode(true).map_err(|e| e.to_string())?;
+            return Ok(opened);
+        }
+    }
+    return Err(format!("Couldn't find a {kind:?}"));
+}
+
+// Which Switch controller variant to drive, chosen by `detect()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Detected {
+    ProController,
+    JoyConLeft,
+    JoyConRight,
+    JoyConPair,
+}
+
+// Probes the HID device list for whichever Switch controller is plugged
+// in: a Pro Controller takes priority, then a paired set of both Joy-Cons,
+// then whichever single Joy-Con is present.
+pub fn detect() -> Result<Detected, String> {
+    let api = HidApi::new().map_err(|e| e.to_string())?;
+    let mut has_left = false;
+    let mut has_right = false;
+    for device in api.device_list() {
+        match DeviceKind::from_ids(device.vendor_id(), device.product_id()) {
+            Some(DeviceKind::ProController) => return Ok(Detected::ProController),
+            Some(DeviceKind::JoyConLeft) => has_left = true,
+            Some(DeviceKind::JoyConRight) => has_right = true,
+            None => {}
+        }
+    }
+    return match (has_left, has_right) {
+        (true, true) => Ok(Detected::JoyConPair),
+        (true, false) => Ok(Detected::JoyConLeft),
+        (false, true) => Ok(Detected::JoyConRight),
+        (false, false) => Err("Couldn't find a Pro Controller or Joy-Con".to_string()),
+    };
+}
@@ -0,0 +1,135 @@
+// Minimal CemuHook/DSU motion server.
+//
+// Implements just enough of the protocol described at
+// https://v1993.github.io/cemuhook-protocol/ for a single pad (slot 0) to
+// stream accelerometer/gyro data to clients such as Cemu or Dolphin.
+
+use std::collections::HashSet;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::motion::MotionFrame;
+
+const DSU_PORT: u16 = 26760;
+const PROTOCOL_VERSION: u16 = 1001;
+const SERVER_ID: u32 = 0x4e4f4f42; // "NOOB"
+
+const MSG_VERSION: u32 = 0x100000;
+const MSG_PORTS: u32 = 0x100001;
+const MSG_PAD_DATA: u32 = 0x100002;
+
+// IEEE 802.3 CRC32, computed with the packet's own CRC field zeroed.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFFFFFF;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = 0u32.wrapping_sub(crc & 1);
+            crc = (crc >> 1) ^ (0xEDB88320 & mask);
+        }
+    }
+    return !crc;
+}
+
+fn write_header(buf: &mut Vec<u8>, msg_type: u32, payload: &[u8]) {
+    buf.extend_from_slice(b"DSUS");
+    buf.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+    buf.extend_from_slice(&((payload.len() as u16 + 4).to_le_bytes()));
+    buf.extend_from_slice(&[0u8; 4]); // CRC32 placeholder
+    buf.extend_from_slice(&SERVER_ID.to_le_bytes());
+    buf.extend_from_slice(&msg_type.to_le_bytes());
+    buf.extend_from_slice(payload);
+    let crc = crc32(buf);
+    buf[8..12].copy_from_slice(&crc.to_le_bytes());
+}
+
+fn version_response() -> Vec<u8> {
+    let mut buf = Vec::new();
+    write_header(&mut buf, MSG_VERSION, &PROTOCOL_VERSION.to_le_bytes());
+    return buf;
+}
+
+fn port_info_response(slot: u8, connected: bool) -> Vec<u8> {
+    let mut payload = Vec::new();
+    payload.push(slot);
+    payload.push(if connected { 2 } else { 0 }); // slot state: connected/disconnected
+    payload.push(2); // device model: full gyro
+    payload.push(1); // connection type: USB
+    payload.extend_from_slice(&[0u8; 6]); // MAC address, unused
+    payload.push(5); // battery: full
+    payload.push(0); // padding
+    let mut buf = Vec::new();
+    write_header(&mut buf, MSG_PORTS, &payload);
+    return buf;
+}
+
+// Streams a single pad's motion sample to every subscribed client.
+pub fn send_pad_data(socket: &UdpSocket, clients: &HashSet<SocketAddr>, packet_number: u32, timestamp_us: u64, frame: MotionFrame) {
+    let mut payload = Vec::new();
+    payload.push(0u8); // slot
+    payload.push(2); // slot state: connected
+    payload.push(2); // device model: full gyro
+    payload.push(1); // connection type: USB
+    payload.extend_from_slice(&[0u8; 6]); // MAC, unused
+    payload.push(5); // battery: full
+    payload.push(1); // is connected
+    payload.extend_from_slice(&packet_number.to_le_bytes());
+    payload.extend_from_slice(&[0u8; 12]); // buttons/dpad/sticks, not tracked here
+    payload.extend_from_slice(&[0u8; 20]); // analog face buttons/shoulders/triggers and two touch points, not tracked here
+    payload.extend_from_slice(&timestamp_us.to_le_bytes());
+    for v in frame.accel {
+        payload.extend_from_slice(&v.to_le_bytes());
+    }
+    for v in frame.gyro {
+        payload.extend_from_slice(&v.to_le_bytes());
+    }
+    let mut buf = Vec::new();
+    write_header(&mut buf, MSG_PAD_DATA, &payload);
+    for addr in clients {
+        let _ = socket.send_to(&buf, addr);
+    }
+}
+
+// Shared set of addresses that have asked for pad data, fed by the request
+// thread spawned in `start` and read by the main loop each time a fresh IMU
+// sample is available. Addresses are never removed.
+pub type Subscribers = Arc<Mutex<HashSet<SocketAddr>>>;
+
+// Binds the DSU UDP socket and handles client requests in a background
+// thread. Returns the socket (for sending pad data) and the shared
+// subscriber set.
+pub fn start() -> Result<(UdpSocket, Subscribers), String> {
+    let socket = UdpSocket::bind(("0.0.0.0", DSU_PORT)).map_err(|e| e.to_string())?;
+    let reader = socket.try_clone().map_err(|e| e.to_string())?;
+    let subscribers: Subscribers = Arc::new(Mutex::new(HashSet::new()));
+    let subscribers_thread = Arc::clone(&subscribers);
+
+    thread::spawn(move || {
+        let mut buf = [0u8; 128];
+        loop {
+            let (len, addr) = match reader.recv_from(&mut buf) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            if len < 20 || &buf[0..4] != b"DSUC" {
+                continue;
+            }
+            let msg_type = u32::from_le_bytes([buf[16], buf[17], buf[18], buf[19]]);
+            match msg_type {
+                MSG_VERSION => {
+                    let _ = reader.send_to(&version_response(), addr);
+                }
+                MSG_PORTS => {
+                    let _ = reader.send_to(&port_info_response(0, true), addr);
+                }
+                MSG_PAD_DATA => {
+                    subscribers_thread.lock().unwrap().insert(addr);
+                }
+                _ => {}
+            }
+        }
+    });
+
+    return Ok((socket, subscribers));
+}
@@ -0,0 +1,311 @@
+// Joy-Con (L), Joy-Con (R) and a fused Joy-Con pair, alongside the Pro
+// Controller in main.rs. All three speak the same protocol as the Pro
+// Controller (see SwitchController), they just differ in device identity,
+// which half of the stick calibration they own, and - for the pair - in
+// fusing two physical units into one virtual pad.
+//
+// Variant list mirrors the Switch gamepad types enumerated by
+// https://github.com/doukutsu-rs/doukutsu-rs (gamepad/mod.rs).
+
+use std::ops::{Deref, DerefMut};
+
+use hidapi::{HidDevice, HidResult};
+
+use crate::device::{self, DeviceKind};
+use crate::switch_controller::SwitchController;
+use crate::{Controller, ControllerIo};
+
+pub struct JoyConLeft(SwitchController);
+pub struct JoyConRight(SwitchController);
+
+impl Deref for JoyConLeft {
+    type Target = SwitchController;
+    fn deref(&self) -> &SwitchController {
+        return &self.0;
+    }
+}
+
+impl DerefMut for JoyConLeft {
+    fn deref_mut(&mut self) -> &mut SwitchController {
+        return &mut self.0;
+    }
+}
+
+impl Deref for JoyConRight {
+    type Target = SwitchController;
+    fn deref(&self) -> &SwitchController {
+        return &self.0;
+    }
+}
+
+impl DerefMut for JoyConRight {
+    fn deref_mut(&mut self) -> &mut SwitchController {
+        return &mut self.0;
+    }
+}
+
+impl Controller for JoyConLeft {
+    fn find_device() -> Result<Vec<HidDevice>, String> {
+        return Ok(vec![device::find_device(DeviceKind::JoyConLeft)?]);
+    }
+
+    fn new(hids: Vec<HidDevice>) -> Self {
+        let hid = hids.into_iter().next().expect("JoyConLeft requires exactly one HID device");
+        return JoyConLeft(SwitchController::new(hid));
+    }
+}
+
+impl ControllerIo for JoyConLeft {
+    fn reset(&mut self) -> Result<(), String> {
+        return self.0.reset();
+    }
+
+    fn handshake(&self) -> Result<(), String> {
+        return self.0.handshake();
+    }
+
+    fn calibrate(&mut self) -> Result<(), String> {
+        self.ldeadzone = self.0.read_stick_calibration(0x8012, 0x603d, 0x6086, true)?;
+        return Ok(());
+    }
+
+    fn set_imu(&mut self, state: bool) -> Result<(), String> {
+        return self.0.set_imu(state);
+    }
+
+    fn set_vibration(&mut self, state: bool) -> Result<(), String> {
+        return self.0.set_vibration(state);
+    }
+
+    fn set_report_mode(&mut self, mode: u8) -> Result<(), String> {
+        return self.0.set_report_mode(mode);
+    }
+
+    fn set_player_lights(&mut self, bitfield: u8) -> Result<(), String> {
+        return self.0.set_player_lights(bitfield);
+    }
+
+    fn read_hid(&self, buf: &mut [u8]) -> HidResult<usize> {
+        return self.0.read_hid(buf);
+    }
+
+    fn send_rumble(&mut self, left_motor: u8, right_motor: u8) -> Result<(), String> {
+        return self.0.send_rumble(left_motor, right_motor);
+    }
+
+    fn lstick_cal(&self) -> [u16; 6] {
+        return self.0.lstick_cal;
+    }
+
+    fn rstick_cal(&self) -> [u16; 6] {
+        return self.0.rstick_cal;
+    }
+
+    fn ldeadzone(&self) -> u16 {
+        return self.0.ldeadzone;
+    }
+
+    fn rdeadzone(&self) -> u16 {
+        return self.0.rdeadzone;
+    }
+
+    fn attach(&mut self) -> Result<(), String> {
+        return crate::switch_controller::attach_sequence(self, DeviceKind::JoyConLeft);
+    }
+}
+
+impl Controller for JoyConRight {
+    fn find_device() -> Result<Vec<HidDevice>, String> {
+        return Ok(vec![device::find_device(DeviceKind::JoyConRight)?]);
+    }
+
+    fn new(hids: Vec<HidDevice>) -> Self {
+        let hid = hids.into_iter().next().expect("JoyConRight requires exactly one HID device");
+        return JoyConRight(SwitchController::new(hid));
+    }
+}
+
+impl ControllerIo for JoyConRight {
+    fn reset(&mut self) -> Result<(), String> {
+        return self.0.reset();
+    }
+
+    fn handshake(&self) -> Result<(), String> {
+        return self.0.handshake();
+    }
+
+    fn calibrate(&mut self) -> Result<(), String> {
+        self.rdeadzone = self.0.read_stick_calibration(0x801d, 0x6046, 0x6098, false)?;
+        return Ok(());
+    }
+
+    fn set_imu(&mut self, state: bool) -> Result<(), String> {
+        return self.0.set_imu(state);
+    }
+
+    fn set_vibration(&mut self, state: bool) -> Result<(), String> {
+        return self.0.set_vibration(state);
+    }
+
+    fn set_report_mode(&mut self, mode: u8) -> Result<(), String> {
+        return self.0.set_report_mode(mode);
+    }
+
+    fn set_player_lights(&mut self, bitfield: u8) -> Result<(), String> {
+        return self.0.set_player_lights(bitfield);
+    }
+
+    fn read_hid(&self, buf: &mut [u8]) -> HidResult<usize> {
+        return self.0.read_hid(buf);
+    }
+
+    fn send_rumble(&mut self, left_motor: u8, right_motor: u8) -> Result<(), String> {
+        return self.0.send_rumble(left_motor, right_motor);
+    }
+
+    fn lstick_cal(&self) -> [u16; 6] {
+        return self.0.lstick_cal;
+    }
+
+    fn rstick_cal(&self) -> [u16; 6] {
+        return self.0.rstick_cal;
+    }
+
+    fn ldeadzone(&self) -> u16 {
+        return self.0.ldeadzone;
+    }
+
+    fn rdeadzone(&self) -> u16 {
+        return self.0.rdeadzone;
+    }
+
+    fn attach(&mut self) -> Result<(), String> {
+        return crate::switch_controller::attach_sequence(self, DeviceKind::JoyConRight);
+    }
+}
+
+// Fuses an opened Joy-Con (L) and Joy-Con (R) into one virtual pad: left
+// stick + L/ZL/dpad from the left unit, right stick + R/ZR/ABXY from the
+// right unit.
+pub struct JoyConPair {
+    pub left: JoyConLeft,
+    pub right: JoyConRight,
+}
+
+impl Controller for JoyConPair {
+    fn find_device() -> Result<Vec<HidDevice>, String> {
+        let left = device::find_device(DeviceKind::JoyConLeft)?;
+        let right = device::find_device(DeviceKind::JoyConRight)?;
+        return Ok(vec![left, right]);
+    }
+
+    fn new(hids: Vec<HidDevice>) -> Self {
+        let mut hids = hids.into_iter();
+        let left_hid = hids.next().expect("JoyConPair requires a left and a right HID device");
+        let right_hid = hids.next().expect("JoyConPair requires a left and a right HID device");
+        return JoyConPair {
+            left: JoyConLeft::new(vec![left_hid]),
+            right: JoyConRight::new(vec![right_hid]),
+        };
+    }
+}
+
+impl ControllerIo for JoyConPair {
+    fn reset(&mut self) -> Result<(), String> {
+        self.left.reset()?;
+        self.right.reset()?;
+        return Ok(());
+    }
+
+    fn handshake(&self) -> Result<(), String> {
+        self.left.handshake()?;
+        self.right.handshake()?;
+        return Ok(());
+    }
+
+    fn calibrate(&mut self) -> Result<(), String> {
+        self.left.calibrate()?;
+        self.right.calibrate()?;
+        return Ok(());
+    }
+
+    fn set_imu(&mut self, state: bool) -> Result<(), String> {
+        self.left.set_imu(state)?;
+        self.right.set_imu(state)?;
+        return Ok(());
+    }
+
+    fn set_vibration(&mut self, state: bool) -> Result<(), String> {
+        self.left.set_vibration(state)?;
+        self.right.set_vibration(state)?;
+        return Ok(());
+    }
+
+    fn set_report_mode(&mut self, mode: u8) -> Result<(), String> {
+        self.left.set_report_mode(mode)?;
+        self.right.set_report_mode(mode)?;
+        return Ok(());
+    }
+
+    fn set_player_lights(&mut self, bitfield: u8) -> Result<(), String> {
+        self.left.set_player_lights(bitfield)?;
+        self.right.set_player_lights(bitfield)?;
+        return Ok(());
+    }
+
+    // Reads one report from each unit and fuses them into a single report
+    // using the same byte layout the Pro Controller sends: the right unit
+    // owns byte 3 (ABXY/R/ZR) and the right stick (bytes 9-11), the left
+    // unit owns byte 5 (dpad/L/ZL) and the left stick (bytes 6-8), and
+    // byte 4 (shared buttons) is OR'd together.
+    // TODO: if only one half disconnects, its read_hid returns Ok(0)/zeroed
+    // bytes but the other half's non-zero report still gets spliced in
+    // below, so the combined report never reads as all-zero. main()'s
+    // staleness check (which drives reattach) can't see a half-dead pair -
+    // this needs its own per-side liveness signal to reconnect correctly.
+    fn read_hid(&self, buf: &mut [u8]) -> HidResult<usize> {
+        let mut left_buf = [0u8; crate::REPORT_LEN];
+        let mut right_buf = [0u8; crate::REPORT_LEN];
+        let left_len = self.left.read_hid(&mut left_buf)?;
+        let right_len = self.right.read_hid(&mut right_buf)?;
+        let len = left_len.max(right_len).min(buf.len());
+        let copy_len = crate::REPORT_LEN.min(buf.len());
+        buf[..copy_len].copy_from_slice(&left_buf[..copy_len]);
+        if buf.len() > 9 {
+            buf[3] = right_buf[3];
+            buf[4] = left_buf[4] | right_buf[4];
+            let rstick_end = 12.min(buf.len());
+            buf[9..rstick_end].copy_from_slice(&right_buf[9..rstick_end]);
+        }
+        return Ok(len);
+    }
+
+    fn send_rumble(&mut self, left_motor: u8, right_motor: u8) -> Result<(), String> {
+        self.left.send_rumble(left_motor, left_motor)?;
+        self.right.send_rumble(right_motor, right_motor)?;
+        return Ok(());
+    }
+
+    // Each half only owns its own side's calibration data.
+    fn lstick_cal(&self) -> [u16; 6] {
+        return self.left.lstick_cal();
+    }
+
+    fn rstick_cal(&self) -> [u16; 6] {
+        return self.right.rstick_cal();
+    }
+
+    fn ldeadzone(&self) -> u16 {
+        return self.left.ldeadzone();
+    }
+
+    fn rdeadzone(&self) -> u16 {
+        return self.right.rdeadzone();
+    }
+
+    fn attach(&mut self) -> Result<(), String> {
+        self.left.attach()?;
+        self.right.attach()?;
+        return Ok(());
+    }
+}
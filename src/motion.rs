@@ -0,0 +1,55 @@
+// IMU parsing for the standard 0x30 input report.
+//
+// A 0x30 report packs three IMU samples (5ms apart) starting at byte 13,
+// each 12 bytes: accel X/Y/Z then gyro X/Y/Z, all little-endian i16.
+// See https://github.com/dekuNukem/Nintendo_Switch_Reverse_Engineering/blob/master/imu_sensor_notes.md
+
+const IMU_OFFSET: usize = 13;
+const FRAME_LEN: usize = 12;
+const FRAME_COUNT: usize = 3;
+
+// Spacing between the three samples packed into one report.
+pub const FRAME_INTERVAL_US: u64 = 5000;
+
+// raw accel counts -> g
+const ACCEL_COEFF: f32 = 1.0 / 4096.0;
+// Factory gyro sensitivity used by BetterJoy to turn raw counts into deg/s.
+const GYRO_SENSITIVITY: f32 = 16.384 * 0.0625;
+const GYRO_COEFF: f32 = 0.0001694 * 57.2958;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MotionFrame {
+    pub accel: [f32; 3],
+    pub gyro: [f32; 3],
+}
+
+fn read_i16_le(buf: &[u8], offset: usize) -> i16 {
+    i16::from_le_bytes([buf[offset], buf[offset + 1]])
+}
+
+// Parses the three 12-byte IMU frames out of a 0x30 standard input report.
+pub fn parse_imu(report: &[u8]) -> [MotionFrame; FRAME_COUNT] {
+    let mut frames = [MotionFrame::default(); FRAME_COUNT];
+    for (i, frame) in frames.iter_mut().enumerate() {
+        let base = IMU_OFFSET + i * FRAME_LEN;
+        let raw: [i16; 6] = [
+            read_i16_le(report, base),
+            read_i16_le(report, base + 2),
+            read_i16_le(report, base + 4),
+            read_i16_le(report, base + 6),
+            read_i16_le(report, base + 8),
+            read_i16_le(report, base + 10),
+        ];
+        frame.accel = [
+            f32::from(raw[0]) * ACCEL_COEFF,
+            f32::from(raw[1]) * ACCEL_COEFF,
+            f32::from(raw[2]) * ACCEL_COEFF,
+        ];
+        frame.gyro = [
+            f32::from(raw[3]) * GYRO_COEFF / GYRO_SENSITIVITY,
+            f32::from(raw[4]) * GYRO_COEFF / GYRO_SENSITIVITY,
+            f32::from(raw[5]) * GYRO_COEFF / GYRO_SENSITIVITY,
+        ];
+    }
+    return frames;
+}
@@ -0,0 +1,241 @@
+// Configurable button remapping: maps each physical input to an arbitrary
+// XInput button or a turbo/toggle variant of one, loaded from a small
+// text config file instead of main()'s old hard-coded bit-shift pile.
+//
+// The per-button state machine (is_pressed/time_pressed/toggle) follows
+// the one in the rust-sdl-test controller.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+// Every physical input the controller can report, including Home and
+// Capture which main() used to parse and drop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PhysicalInput {
+    A,
+    B,
+    X,
+    Y,
+    L,
+    R,
+    Zl,
+    Zr,
+    DpadUp,
+    DpadDown,
+    DpadLeft,
+    DpadRight,
+    Plus,
+    Minus,
+    LStick,
+    RStick,
+    Home,
+    Capture,
+}
+
+impl PhysicalInput {
+    fn from_name(name: &str) -> Option<PhysicalInput> {
+        return match name {
+            "A" => Some(PhysicalInput::A),
+            "B" => Some(PhysicalInput::B),
+            "X" => Some(PhysicalInput::X),
+            "Y" => Some(PhysicalInput::Y),
+            "L" => Some(PhysicalInput::L),
+            "R" => Some(PhysicalInput::R),
+            "ZL" => Some(PhysicalInput::Zl),
+            "ZR" => Some(PhysicalInput::Zr),
+            "DpadUp" => Some(PhysicalInput::DpadUp),
+            "DpadDown" => Some(PhysicalInput::DpadDown),
+            "DpadLeft" => Some(PhysicalInput::DpadLeft),
+            "DpadRight" => Some(PhysicalInput::DpadRight),
+            "Plus" => Some(PhysicalInput::Plus),
+            "Minus" => Some(PhysicalInput::Minus),
+            "LStick" => Some(PhysicalInput::LStick),
+            "RStick" => Some(PhysicalInput::RStick),
+            "Home" => Some(PhysicalInput::Home),
+            "Capture" => Some(PhysicalInput::Capture),
+            _ => None,
+        };
+    }
+}
+
+// What a physical input maps to.
+#[derive(Clone, Copy, Debug)]
+pub enum RemapAction {
+    // Passes straight through to the mapped XInput button while held.
+    Button(u16),
+    // Auto-pulses the mapped XInput button at `hz` while held.
+    Turbo(u16, f32),
+    // Latches the mapped XInput button on until pressed again.
+    Toggle(u16),
+    // Chord: only emits the mapped button once it's been held continuously
+    // for `threshold_secs`, e.g. Home-held emitting the Xbox Guide button.
+    LongPress(u16, f32),
+}
+
+fn xbutton_from_name(name: &str) -> Option<u16> {
+    return match name {
+        "A" => Some(vigem_client::XButtons::A),
+        "B" => Some(vigem_client::XButtons::B),
+        "X" => Some(vigem_client::XButtons::X),
+        "Y" => Some(vigem_client::XButtons::Y),
+        "Up" => Some(vigem_client::XButtons::UP),
+        "Down" => Some(vigem_client::XButtons::DOWN),
+        "Left" => Some(vigem_client::XButtons::LEFT),
+        "Right" => Some(vigem_client::XButtons::RIGHT),
+        "Start" => Some(vigem_client::XButtons::START),
+        "Back" => Some(vigem_client::XButtons::BACK),
+        "LeftShoulder" => Some(vigem_client::XButtons::LB),
+        "RightShoulder" => Some(vigem_client::XButtons::RB),
+        "LeftThumb" => Some(vigem_client::XButtons::LTHUMB),
+        "RightThumb" => Some(vigem_client::XButtons::RTHUMB),
+        "Guide" => Some(vigem_client::XButtons::GUIDE),
+        _ => None,
+    };
+}
+
+#[derive(Clone, Copy)]
+pub struct RemapEntry {
+    pub input: PhysicalInput,
+    pub action: RemapAction,
+}
+
+// Parses one config line of the form `Input=Target`, `Input=Target:turbo:Hz`,
+// `Input=Target:toggle` or `Input=Target:longpress:Seconds`.
+fn parse_line(line: &str) -> Result<Option<RemapEntry>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let (input_name, rest) = line.split_once('=').ok_or_else(|| format!("Invalid remap line: {line}"))?;
+    let input = PhysicalInput::from_name(input_name.trim()).ok_or_else(|| format!("Unknown physical input: {input_name}"))?;
+    let mut parts = rest.trim().split(':');
+    let target = parts.next().unwrap_or("");
+    let bits = xbutton_from_name(target).ok_or_else(|| format!("Unknown remap target: {target}"))?;
+    let action = match parts.next() {
+        Some("turbo") => {
+            let hz: f32 = parts.next().unwrap_or("10").parse().map_err(|_| format!("Invalid turbo rate on line: {line}"))?;
+            RemapAction::Turbo(bits, hz)
+        }
+        Some("toggle") => RemapAction::Toggle(bits),
+        Some("longpress") => {
+            let threshold: f32 = parts.next().unwrap_or("0.5").parse().map_err(|_| format!("Invalid longpress threshold on line: {line}"))?;
+            RemapAction::LongPress(bits, threshold)
+        }
+        Some(other) => return Err(format!("Unknown remap mode '{other}' on line: {line}")),
+        None => RemapAction::Button(bits),
+    };
+    return Ok(Some(RemapEntry { input, action }));
+}
+
+pub fn load(path: &str) -> Result<Vec<RemapEntry>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut entries = Vec::new();
+    for line in contents.lines() {
+        if let Some(entry) = parse_line(line)? {
+            entries.push(entry);
+        }
+    }
+    return Ok(entries);
+}
+
+// Fallback used when the config file is absent/unreadable: the mapping
+// main() used before this module existed, plus the inputs it dropped -
+// stick clicks to the thumb buttons, Capture to Back, and Home held for
+// 0.5s to Guide.
+pub fn default_config() -> Vec<RemapEntry> {
+    let b = |name: &str| xbutton_from_name(name).unwrap();
+    return vec![
+        RemapEntry { input: PhysicalInput::A, action: RemapAction::Button(b("A")) },
+        RemapEntry { input: PhysicalInput::B, action: RemapAction::Button(b("B")) },
+        RemapEntry { input: PhysicalInput::X, action: RemapAction::Button(b("X")) },
+        RemapEntry { input: PhysicalInput::Y, action: RemapAction::Button(b("Y")) },
+        RemapEntry { input: PhysicalInput::DpadUp, action: RemapAction::Button(b("Up")) },
+        RemapEntry { input: PhysicalInput::DpadDown, action: RemapAction::Button(b("Down")) },
+        RemapEntry { input: PhysicalInput::DpadLeft, action: RemapAction::Button(b("Left")) },
+        RemapEntry { input: PhysicalInput::DpadRight, action: RemapAction::Button(b("Right")) },
+        RemapEntry { input: PhysicalInput::Plus, action: RemapAction::Button(b("Start")) },
+        RemapEntry { input: PhysicalInput::Minus, action: RemapAction::Button(b("Back")) },
+        RemapEntry { input: PhysicalInput::L, action: RemapAction::Button(b("LeftShoulder")) },
+        RemapEntry { input: PhysicalInput::R, action: RemapAction::Button(b("RightShoulder")) },
+        RemapEntry { input: PhysicalInput::LStick, action: RemapAction::Button(b("LeftThumb")) },
+        RemapEntry { input: PhysicalInput::RStick, action: RemapAction::Button(b("RightThumb")) },
+        RemapEntry { input: PhysicalInput::Capture, action: RemapAction::Button(b("Back")) },
+        RemapEntry { input: PhysicalInput::Home, action: RemapAction::LongPress(b("Guide"), 0.5) },
+    ];
+}
+
+// Per-button runtime state.
+#[derive(Clone, Copy)]
+struct ButtonState {
+    is_pressed: bool,
+    time_pressed: Option<Instant>,
+    toggle: bool,
+}
+
+impl Default for ButtonState {
+    fn default() -> Self {
+        return ButtonState {
+            is_pressed: false,
+            time_pressed: None,
+            toggle: false,
+        };
+    }
+}
+
+// Drives the configured remap entries frame-by-frame: feed it the set of
+// currently-held physical inputs, get back the fused XInput button bitmask.
+pub struct Remapper {
+    entries: Vec<RemapEntry>,
+    state: HashMap<PhysicalInput, ButtonState>,
+}
+
+impl Remapper {
+    pub fn new(entries: Vec<RemapEntry>) -> Self {
+        return Remapper { entries, state: HashMap::new() };
+    }
+
+    pub fn update(&mut self, held: &[PhysicalInput]) -> u16 {
+        let now = Instant::now();
+        let mut buttons = 0u16;
+        for entry in &self.entries {
+            let state = self.state.entry(entry.input).or_default();
+            let is_held = held.contains(&entry.input);
+
+            if is_held && !state.is_pressed {
+                state.time_pressed = Some(now);
+                state.toggle = !state.toggle;
+            }
+            state.is_pressed = is_held;
+
+            match entry.action {
+                RemapAction::Button(bits) => {
+                    if is_held {
+                        buttons |= bits;
+                    }
+                }
+                RemapAction::Turbo(bits, hz) => {
+                    if is_held {
+                        let since_pressed = now.duration_since(state.time_pressed.unwrap_or(now)).as_secs_f32();
+                        if (since_pressed * hz).fract() < 0.5 {
+                            buttons |= bits;
+                        }
+                    }
+                }
+                RemapAction::Toggle(bits) => {
+                    if state.toggle {
+                        buttons |= bits;
+                    }
+                }
+                RemapAction::LongPress(bits, threshold_secs) => {
+                    if is_held {
+                        let since_pressed = now.duration_since(state.time_pressed.unwrap_or(now)).as_secs_f32();
+                        if since_pressed >= threshold_secs {
+                            buttons |= bits;
+                        }
+                    }
+                }
+            }
+        }
+        return buttons;
+    }
+}
@@ -0,0 +1,40 @@
+// HD rumble encoding for the Pro Controller's 0x10 output report.
+//
+// Each side gets a 4-byte payload encoding a frequency/amplitude pair. We
+// follow BetterJoy's approach of picking one neutral high-band and one
+// neutral low-band frequency and only varying amplitude with the XInput
+// motor value, rather than exposing the full frequency range.
+// See https://github.com/dekuNukem/Nintendo_Switch_Reverse_Engineering/blob/master/rumble_data_table.md
+
+const HIGH_FREQ_HZ: f32 = 160.0;
+const LOW_FREQ_HZ: f32 = 320.0;
+
+// Neutral/no-vibration payload used by the reverse-engineering docs.
+const NEUTRAL: [u8; 4] = [0x00, 0x01, 0x40, 0x40];
+
+fn encode_frequency(freq_hz: f32) -> u8 {
+    (((freq_hz / 10.0).log2() * 32.0).round() as i32).clamp(0, 0xff) as u8
+}
+
+// Clamps into the controller's safe amplitude range and encodes it using
+// the log-scaled curve from the rumble data table.
+fn encode_amplitude(amplitude: f32) -> u8 {
+    let amplitude = amplitude.clamp(0.0, 1.0);
+    if amplitude <= 0.0 {
+        return 0;
+    }
+    (((amplitude.log2() * 8.0) + 0x60 as f32).round() as i32).clamp(0, 0xff) as u8
+}
+
+// Encodes one side's 4-byte HD rumble payload from an XInput motor value (0-255).
+pub fn encode_motor(motor_value: u8) -> [u8; 4] {
+    if motor_value == 0 {
+        return NEUTRAL;
+    }
+    let amplitude = f32::from(motor_value) / 255.0;
+    let hf = encode_frequency(HIGH_FREQ_HZ);
+    let hf_amp = encode_amplitude(amplitude);
+    let lf = encode_frequency(LOW_FREQ_HZ) | 0x1;
+    let lf_amp = encode_amplitude(amplitude);
+    return [hf, hf_amp, lf, lf_amp];
+}
@@ -0,0 +1,52 @@
+// Analog stick response shaping: centers a raw stick reading against its
+// calibration, then reshapes it in magnitude/angle space (as the
+// rust-sdl-test `Stick` does with `atan2`) instead of a hard circular
+// cutoff, so output ramps smoothly from the deadzone edge to full tilt.
+
+#[derive(Clone, Copy, Debug)]
+pub struct StickSettings {
+    // Fraction (0.0-1.0) of the calibrated range below which input is ignored.
+    pub inner_deadzone: f32,
+    // Fraction (0.0-1.0) of the calibrated range at which output saturates to full tilt.
+    pub outer_radius: f32,
+    // Response curve exponent applied to the remapped magnitude; 1.0 is linear.
+    pub curve_exponent: f32,
+    // Minimum output once past the inner deadzone, to overcome a game's own deadzone.
+    pub anti_deadzone: f32,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        return StickSettings {
+            inner_deadzone: 0.1,
+            outer_radius: 0.98,
+            curve_exponent: 1.0,
+            anti_deadzone: 0.0,
+        };
+    }
+}
+
+// Centers `vals` against the stick's calibration data and applies the
+// scaled radial deadzone / response curve / anti-deadzone pipeline
+// described by `settings`. Returns normalized [-1.0, 1.0] X/Y.
+pub fn center_and_shape(vals: [u16; 2], cal: [u16; 6], settings: &StickSettings) -> [f32; 2] {
+    let dx = f32::from(vals[0]) - f32::from(cal[2]);
+    let dy = f32::from(vals[1]) - f32::from(cal[3]);
+    let nx = dx / if dx > 0.0 { f32::from(cal[0]) } else { f32::from(cal[4]) };
+    let ny = dy / if dy > 0.0 { f32::from(cal[1]) } else { f32::from(cal[5]) };
+
+    let magnitude = (nx * nx + ny * ny).sqrt();
+    if magnitude <= settings.inner_deadzone {
+        return [0.0, 0.0];
+    }
+    let angle = ny.atan2(nx);
+
+    let span = (settings.outer_radius - settings.inner_deadzone).max(f32::EPSILON);
+    let mut shaped = ((magnitude - settings.inner_deadzone) / span).clamp(0.0, 1.0);
+    shaped = shaped.powf(settings.curve_exponent);
+    if settings.anti_deadzone > 0.0 {
+        shaped = settings.anti_deadzone + shaped * (1.0 - settings.anti_deadzone);
+    }
+
+    return [shaped * angle.cos(), shaped * angle.sin()];
+}
@@ -0,0 +1,219 @@
+// Shared low-level protocol implementation for every Switch controller
+// variant (Pro Controller, Joy-Con (L), Joy-Con (R)). They all speak the
+// same USB/Bluetooth HID protocol and the same 0x30 standard input report
+// layout; what differs between them is device identity and which half of
+// the stick/button data is meaningful, which is handled by the
+// device-specific wrappers (ProController in main.rs, the Joy-Cons in
+// joycon.rs).
+//
+// From https://github.com/Davidobot/BetterJoy/blob/461f5f8f5c0368eeae8dfdf27536bc8cb906ac19/BetterJoyForCemu/Joycon.cs
+// Lots of help from https://github.com/dekuNukem/Nintendo_Switch_Reverse_Engineering/
+
+use std::ops::DerefMut;
+use std::thread;
+use std::time::Duration;
+
+use hidapi::{HidDevice, HidResult};
+
+use crate::device::{self, DeviceKind};
+use crate::{rumble, Controller, REPORT_LEN};
+
+pub struct SwitchController {
+    pub hid: HidDevice,
+    pub global_count: u8,
+    pub lstick_cal: [u16; 6],
+    pub rstick_cal: [u16; 6],
+    pub ldeadzone: u16,
+    pub rdeadzone: u16,
+}
+
+impl SwitchController {
+    pub fn new(hid: HidDevice) -> Self {
+        return SwitchController {
+            hid: hid,
+            global_count: 0,
+            lstick_cal: [0u16; 6],
+            rstick_cal: [0u16; 6],
+            ldeadzone: 0,
+            rdeadzone: 0,
+        };
+    }
+
+    pub fn subcommand(&mut self, sc: u8, send: &[u8], recv: &mut [u8]) -> Result<(), String> {
+        let default_buf: [u8; 8] = [0x0, 0x1, 0x40, 0x40, 0x0, 0x1, 0x40, 0x40];
+        let mut buf_ = [0u8; REPORT_LEN];
+        buf_[2..10].copy_from_slice(&default_buf);
+        buf_[11..(11 + send.len())].copy_from_slice(send);
+        buf_[10] = sc;
+        buf_[1] = self.global_count;
+        buf_[0] = 0x1;
+        if self.global_count == 0xf {
+            self.global_count = 0;
+        } else {
+            self.global_count += 1;
+        }
+        if let Err(e) = self.hid.write(&buf_) {
+            return Err(e.to_string());
+        }
+        let mut tries = 0;
+        let mut result;
+        loop {
+            result = self.hid.read_timeout(recv, 100);
+            tries += 1;
+            if !(tries < 10 && recv[0] != 0x21 && recv[14] != sc) {
+                break;
+            }
+        }
+        if result.is_err() {
+            return Err(result.err().unwrap().to_string());
+        }
+        return Ok(());
+    }
+
+    pub fn void_subcommand(&mut self, sc: u8, send: &[u8]) -> Result<(), String> {
+        return self.subcommand(sc, send, &mut [0u8; 16]);
+    }
+
+    pub fn reset(&mut self) -> Result<(), String> {
+        return self.void_subcommand(0x06, &[0x04]);
+    }
+
+    pub fn x80_write(&self, buf: &mut [u8; 64], code: u8) -> Result<(), String> {
+        buf[0] = 0x80;
+        buf[1] = code;
+        if let Err(e) = self.hid.write(buf) {
+            return Err(e.to_string());
+        }
+        let _ = self.hid.read_timeout(&mut [], 100);
+        return Ok(());
+    }
+
+    pub fn handshake(&self) -> Result<(), String> {
+        let mut buf = [0u8; 64];
+        // Handshake
+        self.x80_write(&mut buf, 0x2)?;
+        // 3Mbit Baudrate
+        self.x80_write(&mut buf, 0x3)?;
+        // Handshake again
+        self.x80_write(&mut buf, 0x2)?;
+        // Force USB HID only
+        self.x80_write(&mut buf, 0x4)?;
+        return Ok(());
+    }
+
+    pub fn read_spi(&mut self, from: i32, size: u8) -> Result<Vec<u8>, String> {
+        if size > 0x1d {
+            return Err(format!("Reading size {size} > 0x1d"));
+        }
+        let mut cmd = [0xff, 0xff, 0x00, 0x00, size];
+        cmd[0..4].copy_from_slice(&from.to_le_bytes());
+        let mut buf_ = [0u8; REPORT_LEN];
+        self.subcommand(0x10, &cmd, &mut buf_)?;
+        let res = buf_[20..(20 + usize::from(size))].to_owned();
+        return Ok(res);
+    }
+
+    pub fn read_stick_calibration(&mut self, user_address: i32, factory_address: i32, deadzone_address: i32, side: bool) -> Result<u16, String> {
+        let mut buf_ = self.read_spi(user_address, 9)?;
+        let mut found = false;
+        let side_name = if side { "Left" } else { "Right" };
+        for i in buf_.iter() {
+            if *i == 0xff || *i == 0x00 {
+                continue;
+            }
+            println!("Using user calibration data for {side_name}");
+            found = true;
+        }
+        if !found {
+            println!("Using factory calibration data for {side_name}");
+            buf_ = self.read_spi(factory_address, 9)?;
+        }
+        let stick_cal = if side { &mut self.lstick_cal } else { &mut self.rstick_cal };
+        let stick_indexes = if side { [0usize, 1, 2, 3, 4, 5] } else { [2, 3, 4, 5, 0, 1] };
+        stick_cal[stick_indexes[0]] = (u16::from(buf_[1]) << 8) & 0xF00 | u16::from(buf_[0]);
+        stick_cal[stick_indexes[1]] = (u16::from(buf_[2]) << 4) | (u16::from(buf_[1]) >> 4);
+        stick_cal[stick_indexes[2]] = (u16::from(buf_[4]) << 8) & 0xF00 | u16::from(buf_[3]);
+        stick_cal[stick_indexes[3]] = (u16::from(buf_[5]) << 4) | (u16::from(buf_[4]) >> 4);
+        stick_cal[stick_indexes[4]] = (u16::from(buf_[7]) << 8) & 0xF00 | u16::from(buf_[6]);
+        stick_cal[stick_indexes[5]] = (u16::from(buf_[8]) << 4) | (u16::from(buf_[7]) >> 4);
+        buf_ = self.read_spi(deadzone_address, 16)?;
+        let deadzone = (u16::from(buf_[4]) << 8) & 0xF00 | u16::from(buf_[3]);
+        return Ok(deadzone);
+    }
+
+    pub fn set_imu(&mut self, state: bool) -> Result<(), String> {
+        return self.void_subcommand(0x40, &[if state { 0x01 } else { 0x00 }]);
+    }
+
+    pub fn set_vibration(&mut self, state: bool) -> Result<(), String> {
+        return self.void_subcommand(0x48, &[if state { 0x01 } else { 0x00 }]);
+    }
+
+    pub fn set_report_mode(&mut self, mode: u8) -> Result<(), String> {
+        return self.void_subcommand(0x03, &[mode]);
+    }
+
+    pub fn set_player_lights(&mut self, bitfield: u8) -> Result<(), String> {
+        return self.void_subcommand(0x30, &[bitfield]);
+    }
+
+    pub fn read_hid(&self, buf: &mut [u8]) -> HidResult<usize> {
+        return self.hid.read(buf);
+    }
+
+    pub fn send_rumble(&mut self, left_motor: u8, right_motor: u8) -> Result<(), String> {
+        let mut buf_ = [0u8; REPORT_LEN];
+        buf_[0] = 0x10;
+        buf_[1] = self.global_count;
+        if self.global_count == 0xf {
+            self.global_count = 0;
+        } else {
+            self.global_count += 1;
+        }
+        buf_[2..6].copy_from_slice(&rumble::encode_motor(left_motor));
+        buf_[6..10].copy_from_slice(&rumble::encode_motor(right_motor));
+        if let Err(e) = self.hid.write(&buf_) {
+            return Err(e.to_string());
+        }
+        return Ok(());
+    }
+
+}
+
+// Shared reattach/handshake sequence for the Pro Controller, Joy-Con (L)
+// and Joy-Con (R) (the pair drives its two halves through their own
+// `attach()` instead, so it doesn't go through here): poke the device
+// into simple HID mode, retry the whole attach on no response, then run
+// the variant's own `calibrate()` - ldeadzone/rdeadzone differ per
+// variant - before switching into the full 0x30 standard report mode.
+// `kind` is only needed to re-find the device on the retry path.
+pub fn attach_sequence<C>(controller: &mut C, kind: DeviceKind) -> Result<(), String>
+where
+    C: Controller + DerefMut<Target = SwitchController>,
+{
+    controller.global_count = 0;
+    if let Err(e) = controller.hid.write(&[0x80, 0x1]) {
+        return Err(e.to_string());
+    }
+    let mut buf = [0u8; 256];
+    let read = controller.hid.read_timeout(&mut buf[..], 100);
+    if read.is_err() {
+        return Err(read.err().unwrap().to_string());
+    }
+    if buf[0] != 0x81 {
+        controller.reset()?;
+        thread::sleep(Duration::from_millis(6000));
+        controller.hid = device::find_device(kind)?;
+        // !! Simple hid to make sure we catch 0x81 next time !!
+        controller.set_report_mode(0x3f)?;
+        return attach_sequence(controller, kind);
+    }
+    controller.handshake()?;
+    controller.calibrate()?;
+    controller.set_imu(true)?;
+    controller.set_vibration(true)?;
+    controller.set_player_lights(0b00001000)?;
+    // 60hz
+    controller.set_report_mode(0x30)?;
+    return Ok(());
+}